@@ -6,8 +6,8 @@ mod str;
 mod switch;
 
 #[doc(inline)]
-pub use crate::str::Str;
+pub use crate::str::{Conversion, ConversionError, DStr, Value};
 #[doc(inline)]
-pub use crate::switch::Switch;
+pub use crate::switch::{CancelToken, Switch};
 #[doc(inline)]
 pub use progress::*;
@@ -1,12 +1,19 @@
 //! Progress items.
 use crate::Switch;
 use crossbeam_channel::*;
+use futures::Stream;
 use parking_lot::Mutex;
-use std::{borrow::Cow, fmt, sync::Arc};
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{self, Read, Write},
+    sync::Arc,
+};
 
 // ###### PROGRESS #############################################################
 /// An in-progress report.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Progress {
     /// A progress message, if any.
     pub msg: Cow<'static, str>,
@@ -71,17 +78,39 @@ impl ProgressTx {
         self.publisher.send(progress).ok();
     }
 
+    /// Send a progress report from within an async context.
+    ///
+    /// A thin awaitable wrapper over [`send`](ProgressTx::send); publishing is itself non-blocking,
+    /// so this exists purely so callers already in a future can reach for an `.await` surface
+    /// rather than the blocking method.
+    pub async fn send_async<P, M>(&self, pct: P, msg: M)
+    where
+        P: Into<Option<u8>>,
+        M: Into<Cow<'static, str>>,
+    {
+        self.send(pct, msg)
+    }
+
     /// Flag has been set to cancel the current processing.
     pub fn cancelled(&self) -> bool {
         self.cancel.get()
     }
+
+    /// The reason the current processing was asked to stop, if one was given.
+    ///
+    /// Only meaningful once [`cancelled`](ProgressTx::cancelled) reports `true`; lets in-flight
+    /// work surface _why_ it is being wound down.
+    pub fn cancel_reason(&self) -> Option<crate::DStr> {
+        self.cancel.reason()
+    }
 }
 
 // ###### BROADCAST ############################################################
 /// A broadcasting topic, which can be subscribed or published to.
 #[derive(Default)]
 pub struct Topic<T> {
-    subscribers: Arc<Mutex<Vec<Sender<T>>>>,
+    subscribers: Arc<Mutex<Vec<Subscription<T>>>>,
+    conflating: bool,
 }
 
 /// A publisher, able to send to the topic to be broadcast.
@@ -89,11 +118,38 @@ pub type Publisher<T> = Sender<T>;
 /// A subscriber, able to receive from the topic when a broadcast happens.
 pub type Subscriber<T> = Receiver<T>;
 
+/// A registered subscriber, as seen from inside the topic.
+///
+/// For a conflating topic a cloned [`Receiver`] is retained alongside the [`Sender`] so the
+/// publisher can pop the stale value from the `bounded(1)` slot before pushing the newest one —
+/// the `Sender` half cannot drain the channel itself. Because that retained handle keeps the
+/// channel connected, a dropped consumer is detected by the receiver count falling to the lone
+/// drain handle rather than by a `Disconnected` send error.
+struct Subscription<T> {
+    tx: Sender<T>,
+    drain: Option<Receiver<T>>,
+}
+
 impl<T> Topic<T> {
     /// Create a new topic, which can be subscribed and published to.
     pub fn new() -> Self {
         Topic {
             subscribers: Arc::new(Mutex::new(Vec::new())),
+            conflating: false,
+        }
+    }
+
+    /// Create a new _conflating_ topic, where only the latest broadcast is ever observed.
+    ///
+    /// Subscribers of a conflating topic each get a `bounded(1)` channel whose slot is
+    /// overwritten on every publication, so a slow or stalled subscriber can never cause
+    /// unbounded memory growth and always observes the most recent value rather than replaying a
+    /// backlog of stale updates. The publisher never blocks. Disconnect-pruning is unchanged: a
+    /// closed channel removes the subscriber.
+    pub fn latest_only() -> Self {
+        Topic {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            conflating: true,
         }
     }
 
@@ -101,12 +157,71 @@ impl<T> Topic<T> {
     ///
     /// If the topic has been poisoned, an error will be returned.
     pub fn subscribe(&mut self) -> Subscriber<T> {
-        let (tx, rx) = unbounded();
-        let mut subs = self.subscribers.lock();
-        subs.push(tx);
+        let (tx, rx) = if self.conflating {
+            bounded(1)
+        } else {
+            unbounded()
+        };
+        // A conflating subscriber keeps a draining handle in the topic so the publisher can pop a
+        // stale value before overwriting it.
+        let drain = self.conflating.then(|| rx.clone());
+        self.subscribers.lock().push(Subscription { tx, drain });
         rx
     }
 
+    /// Subscribe to the topic as an asynchronous [`Stream`].
+    ///
+    /// The returned stream yields each broadcast as it arrives and terminates (yielding `None`)
+    /// once the topic and all its publishers have been dropped, matching the disconnect-pruning
+    /// behaviour of the blocking [`Topic::subscribe`]. A new subscriber is registered just like a
+    /// blocking one; a small forwarding thread pumps it onto an async channel so the stream can be
+    /// awaited from inside a tokio/async-std executor without blocking.
+    ///
+    /// On a [conflating](Topic::latest_only) topic the stream honours the same latest-only
+    /// guarantee: the bridge is itself a `bounded(1)` async channel whose slot is overwritten, so
+    /// a slow consumer always observes the most recent value rather than a backlog.
+    pub fn subscribe_stream(&mut self) -> impl Stream<Item = T>
+    where
+        T: Send + 'static,
+    {
+        let conflating = self.conflating;
+        let rx = self.subscribe();
+
+        let (atx, arx) = if conflating {
+            async_channel::bounded(1)
+        } else {
+            async_channel::unbounded()
+        };
+        // Only a conflating bridge needs to drain. That retained receiver keeps the channel open,
+        // so a dropped stream is detected by the receiver count falling to the lone drain handle;
+        // an unbounded bridge holds no drain and simply observes `Closed`.
+        let drain = conflating.then(|| arx.clone());
+        std::thread::spawn(move || {
+            for publication in rx.iter() {
+                if drain.is_some() && atx.receiver_count() <= 1 {
+                    return; // the stream's receiver was dropped
+                }
+                let mut item = publication;
+                loop {
+                    match atx.try_send(item) {
+                        Ok(()) => break,
+                        Err(async_channel::TrySendError::Full(returned)) => {
+                            item = returned;
+                            if let Some(drain) = &drain {
+                                drain.try_recv().ok();
+                            }
+                        }
+                        Err(async_channel::TrySendError::Closed(_)) => return,
+                    }
+                }
+            }
+        });
+
+        futures::stream::unfold(arx, |arx| async move {
+            arx.recv().await.ok().map(|item| (item, arx))
+        })
+    }
+
     /// Add a publisher to the topic.
     pub fn add_publisher(&mut self) -> Publisher<T>
     where
@@ -114,26 +229,40 @@ impl<T> Topic<T> {
     {
         let (tx, rx) = unbounded();
         let subs = Arc::clone(&self.subscribers);
-        std::thread::spawn(move || recv_publications(rx, &subs));
+        let conflating = self.conflating;
+        std::thread::spawn(move || recv_publications(rx, &subs, conflating));
         tx
     }
 }
 
-fn recv_publications<T: Clone>(publisher: Receiver<T>, subs: &Mutex<Vec<Sender<T>>>) {
+impl Topic<Progress> {
+    /// Build a [`ProgressTx`] publishing onto this topic, with the given cancel switch.
+    ///
+    /// Pair this with [`Topic::latest_only`] when only the newest [`Progress`] matters.
+    pub fn progress_tx(&mut self, cancel: Switch) -> ProgressTx {
+        ProgressTx::new(self.add_publisher(), cancel)
+    }
+}
+
+fn recv_publications<T: Clone>(
+    publisher: Receiver<T>,
+    subs: &Mutex<Vec<Subscription<T>>>,
+    conflating: bool,
+) {
     // receives until channel becomes empty and disconnected
     for publication in publisher.iter() {
-        send_or_remove(&mut subs.lock(), publication);
+        send_or_remove(&mut subs.lock(), publication, conflating);
     }
 }
 
-fn send_or_remove<T: Clone>(subscribers: &mut Vec<Sender<T>>, item: T) {
+fn send_or_remove<T: Clone>(subscribers: &mut Vec<Subscription<T>>, item: T, conflating: bool) {
     if subscribers.is_empty() {
         return;
     }
 
     let mut i = 0;
     while i < (subscribers.len() - 1) {
-        match subscribers[i].send(item.clone()) {
+        match deliver(&subscribers[i], item.clone(), conflating) {
             Ok(_) => i += 1,
             Err(_) => {
                 subscribers.remove(i);
@@ -143,8 +272,216 @@ fn send_or_remove<T: Clone>(subscribers: &mut Vec<Sender<T>>, item: T) {
 
     if !subscribers.is_empty() {
         debug_assert_eq!(subscribers.len() - 1, i);
-        if subscribers[i].send(item).is_err() {
+        if deliver(&subscribers[i], item, conflating).is_err() {
             subscribers.remove(i);
         }
     }
 }
+
+/// Hand `item` to a single subscriber, returning `Err` if the subscriber has disconnected and
+/// should be pruned.
+///
+/// A conflating subscriber overwrites rather than queues: the newest value always wins. Because a
+/// crossbeam `Sender` cannot drain its own channel, the publisher pops the stale value through the
+/// subscription's retained `Receiver` handle before retrying the push. The push is retried (never
+/// dropping `item`) until it lands; held under the `subscribers` lock, no other producer can
+/// contend for the slot, so this terminates after at most one drain.
+///
+/// The retained drain handle means a conflating send never observes `Disconnected`, so a dropped
+/// consumer is pruned by checking that a receiver other than our own drain still exists.
+fn deliver<T>(sub: &Subscription<T>, item: T, conflating: bool) -> Result<(), ()> {
+    if !conflating {
+        return sub.tx.send(item).map_err(|_| ());
+    }
+
+    // The only retained receiver is our drain handle: the real consumer has gone, so prune.
+    if sub.tx.receiver_count() <= 1 {
+        return Err(());
+    }
+
+    let mut item = item;
+    loop {
+        match sub.tx.try_send(item) {
+            Ok(_) => return Ok(()),
+            Err(TrySendError::Full(returned)) => {
+                item = returned;
+                if let Some(drain) = &sub.drain {
+                    drain.try_recv().ok();
+                }
+            }
+            Err(TrySendError::Disconnected(_)) => return Err(()),
+        }
+    }
+}
+
+// ###### WIRE BRIDGE ##########################################################
+// A length-prefixed CBOR framing of `Progress`, used to carry progress across a process
+// boundary. Each frame is a `u32` big-endian byte length followed by the CBOR-encoded `Progress`.
+
+/// Reads [`Progress`] frames off any [`Read`] and republishes them into a local [`Topic`].
+///
+/// This is the receiving half of the cross-process bridge: pair it with a [`ProgressSource`]
+/// running in the worker process so that a host UI can watch a subprocess's progress without a
+/// full RPC stack.
+pub struct ProgressSink {
+    publisher: Publisher<Progress>,
+}
+
+impl ProgressSink {
+    /// Construct a sink which republishes decoded frames through `publisher`.
+    pub fn new(publisher: Publisher<Progress>) -> Self {
+        Self { publisher }
+    }
+
+    /// Construct a sink publishing into `topic`.
+    pub fn from_topic(topic: &mut Topic<Progress>) -> Self {
+        Self::new(topic.add_publisher())
+    }
+
+    /// Decode frames from `rd` until EOF, forwarding each into the topic.
+    ///
+    /// Returns once the stream reaches a clean EOF. Dropping the local topic does not end the
+    /// loop: the topic's forwarding thread keeps the publisher channel open for as long as this
+    /// sink holds its publisher, so frames keep being decoded until EOF or a read error.
+    pub fn run<R: Read>(self, mut rd: R) -> io::Result<()> {
+        loop {
+            let mut len = [0u8; 4];
+            match rd.read_exact(&mut len) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            }
+
+            let mut buf = vec![0u8; u32::from_be_bytes(len) as usize];
+            rd.read_exact(&mut buf)?;
+            let progress = serde_cbor::from_slice(&buf)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            if self.publisher.send(progress).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Subscribes to a local [`Topic`] and writes each [`Progress`] as a CBOR frame to any [`Write`].
+///
+/// This is the sending half of the cross-process bridge; see [`ProgressSink`] for the receiving
+/// half and the frame format.
+pub struct ProgressSource {
+    subscriber: Subscriber<Progress>,
+}
+
+impl ProgressSource {
+    /// Construct a source reading broadcasts from `subscriber`.
+    pub fn new(subscriber: Subscriber<Progress>) -> Self {
+        Self { subscriber }
+    }
+
+    /// Construct a source subscribed to `topic`.
+    pub fn from_topic(topic: &mut Topic<Progress>) -> Self {
+        Self::new(topic.subscribe())
+    }
+
+    /// Encode each broadcast as a length-prefixed CBOR frame and write it to `wr`.
+    ///
+    /// Returns once the topic and all its publishers have been dropped and the subscriber channel
+    /// drains, or on the first encoding or write error.
+    pub fn run<W: Write>(self, mut wr: W) -> io::Result<()> {
+        for progress in self.subscriber.iter() {
+            let buf = serde_cbor::to_vec(&progress)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            wr.write_all(&(buf.len() as u32).to_be_bytes())?;
+            wr.write_all(&buf)?;
+            wr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(pct: u8) -> Progress {
+        Progress {
+            msg: Cow::Borrowed(""),
+            pct,
+        }
+    }
+
+    #[test]
+    fn conflating_keeps_newest() {
+        let (tx, rx) = bounded(1);
+        let mut subs = vec![Subscription {
+            tx,
+            drain: Some(rx.clone()),
+        }];
+
+        for pct in [1, 2, 3] {
+            send_or_remove(&mut subs, progress(pct), true);
+        }
+
+        assert_eq!(subs.len(), 1);
+        assert_eq!(rx.recv().unwrap().pct, 3);
+    }
+
+    #[test]
+    fn conflating_prunes_dropped_subscriber() {
+        let (tx, rx) = bounded(1);
+        let mut subs = vec![Subscription {
+            tx,
+            drain: Some(rx.clone()),
+        }];
+
+        drop(rx); // the consumer is gone; only the drain handle remains
+        send_or_remove(&mut subs, progress(1), true);
+
+        assert!(subs.is_empty());
+    }
+
+    #[test]
+    fn stream_terminates_on_topic_drop() {
+        use futures::StreamExt;
+
+        let mut topic = Topic::new();
+        let stream = topic.subscribe_stream();
+        let publisher = topic.add_publisher();
+
+        publisher.send(progress(5)).unwrap();
+        drop(publisher);
+        drop(topic);
+
+        // `collect` only returns once the stream yields `None`, so reaching this assertion proves
+        // the bridge terminated; any value it did forward must be the one published.
+        let collected = futures::executor::block_on(stream.collect::<Vec<_>>());
+        assert!(collected.iter().all(|p| p.pct == 5));
+    }
+
+    #[test]
+    fn cbor_wire_round_trip() {
+        let sent = Progress {
+            msg: Cow::Borrowed("halfway"),
+            pct: 50,
+        };
+
+        // Encode a frame out of a source topic.
+        let mut src = Topic::new();
+        let source = ProgressSource::from_topic(&mut src);
+        let publisher = src.add_publisher();
+        publisher.send(sent.clone()).unwrap();
+        drop(publisher);
+        drop(src);
+        let mut buf = Vec::new();
+        source.run(&mut buf).unwrap();
+
+        // Decode it back into a sink topic.
+        let mut dst = Topic::new();
+        let rx = dst.subscribe();
+        let sink = ProgressSink::from_topic(&mut dst);
+        sink.run(io::Cursor::new(buf)).unwrap();
+        drop(dst);
+
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![sent]);
+    }
+}
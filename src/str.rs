@@ -285,6 +285,157 @@ fn stack_as_str(s: &StrArr) -> &str {
     unsafe { std::str::from_utf8_unchecked(&(s.1)[..s.0 as usize]) }
 }
 
+// ########### TYPED CONVERSIONS ##############################################
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone};
+
+/// How to interpret the contents of a [`DStr`] as a concrete scalar.
+///
+/// A `Conversion` can be parsed from a short alias with [`FromStr`](std::str::FromStr) (see the
+/// impl for the accepted names), or constructed directly — the `TimestampFmt` variants take a
+/// [chrono format string](chrono::format::strftime) and so have no alias.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conversion {
+    /// Leave the string as-is.
+    Bytes,
+    /// Parse as a signed integer.
+    Integer,
+    /// Parse as a floating point number.
+    Float,
+    /// Parse as a boolean.
+    Boolean,
+    /// Parse as a timestamp, trying a set of default RFC3339/ISO formats.
+    Timestamp,
+    /// Parse as a timestamp against a chrono format string, assuming local time.
+    TimestampFmt(String),
+    /// Parse as a timestamp against a chrono format string which must carry an explicit offset.
+    TimestampTzFmt(String),
+}
+
+/// The typed value produced by [`DStr::convert`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// The unaltered string.
+    Bytes(DStr),
+    /// A signed integer.
+    Integer(i64),
+    /// A floating point number.
+    Float(f64),
+    /// A boolean.
+    Boolean(bool),
+    /// A timestamp with a fixed UTC offset: the offset given in the input, or the local offset
+    /// when the input has none.
+    Timestamp(DateTime<FixedOffset>),
+}
+
+/// An error raised while parsing a [`Conversion`] or applying one to a [`DStr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The alias given to [`Conversion::from_str`](std::str::FromStr) was not recognised.
+    UnknownConversion {
+        /// The unrecognised alias.
+        name: String,
+    },
+    /// The string could not be parsed as the requested type.
+    Parse {
+        /// The conversion that was attempted.
+        ty: &'static str,
+        /// The input that failed to parse.
+        input: String,
+    },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionError::UnknownConversion { name } => {
+                write!(f, "unknown conversion '{}'", name)
+            }
+            ConversionError::Parse { ty, input } => {
+                write!(f, "could not parse '{}' as {}", input, ty)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl std::str::FromStr for Conversion {
+    type Err = ConversionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "asis" | "bytes" | "string" => Conversion::Bytes,
+            "int" | "integer" => Conversion::Integer,
+            "float" => Conversion::Float,
+            "bool" | "boolean" => Conversion::Boolean,
+            "timestamp" => Conversion::Timestamp,
+            _ => {
+                return Err(ConversionError::UnknownConversion {
+                    name: s.to_owned(),
+                })
+            }
+        })
+    }
+}
+
+impl DStr {
+    /// Interpret the string's contents as the scalar described by `conv`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use divvy::{DStr, Conversion, Value};
+    /// let n = DStr::from("42");
+    /// assert_eq!(n.convert(&Conversion::Integer), Ok(Value::Integer(42)));
+    /// ```
+    pub fn convert(&self, conv: &Conversion) -> Result<Value, ConversionError> {
+        let s = self.as_str();
+        let parse = |ty| ConversionError::Parse {
+            ty,
+            input: s.to_owned(),
+        };
+        Ok(match conv {
+            Conversion::Bytes => Value::Bytes(self.clone()),
+            Conversion::Integer => Value::Integer(s.parse().map_err(|_| parse("integer"))?),
+            Conversion::Float => Value::Float(s.parse().map_err(|_| parse("float"))?),
+            Conversion::Boolean => Value::Boolean(s.parse().map_err(|_| parse("boolean"))?),
+            Conversion::Timestamp => Value::Timestamp(parse_default_timestamp(s).ok_or_else(
+                || parse("timestamp"),
+            )?),
+            Conversion::TimestampFmt(fmt) => {
+                let naive =
+                    NaiveDateTime::parse_from_str(s, fmt).map_err(|_| parse("timestamp"))?;
+                let local = Local
+                    .from_local_datetime(&naive)
+                    .single()
+                    .ok_or_else(|| parse("timestamp"))?;
+                Value::Timestamp(local.with_timezone(local.offset()))
+            }
+            Conversion::TimestampTzFmt(fmt) => {
+                Value::Timestamp(DateTime::parse_from_str(s, fmt).map_err(|_| parse("timestamp"))?)
+            }
+        })
+    }
+}
+
+/// Try the default RFC3339/ISO timestamp formats, in order of preference.
+fn parse_default_timestamp(s: &str) -> Option<DateTime<FixedOffset>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt);
+    }
+    // A naive datetime (no offset) is taken to be local time.
+    for fmt in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, fmt) {
+            let local = Local.from_local_datetime(&naive).single()?;
+            return Some(local.with_timezone(local.offset()));
+        }
+        if let Ok(date) = chrono::NaiveDate::parse_from_str(s, fmt) {
+            let naive = date.and_hms_opt(0, 0, 0)?;
+            let local = Local.from_local_datetime(&naive).single()?;
+            return Some(local.with_timezone(local.offset()));
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -381,6 +532,62 @@ mod tests {
         assert_eq!(a1.cmp(&a2), cmp::Ordering::Equal);
     }
 
+    #[test]
+    fn conversion_from_str() {
+        use std::str::FromStr;
+        assert_eq!(Conversion::from_str("asis"), Ok(Conversion::Bytes));
+        assert_eq!(Conversion::from_str("string"), Ok(Conversion::Bytes));
+        assert_eq!(Conversion::from_str("int"), Ok(Conversion::Integer));
+        assert_eq!(Conversion::from_str("integer"), Ok(Conversion::Integer));
+        assert_eq!(Conversion::from_str("float"), Ok(Conversion::Float));
+        assert_eq!(Conversion::from_str("boolean"), Ok(Conversion::Boolean));
+        assert_eq!(Conversion::from_str("timestamp"), Ok(Conversion::Timestamp));
+        assert_eq!(
+            Conversion::from_str("nope"),
+            Err(ConversionError::UnknownConversion {
+                name: "nope".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn convert_scalars() {
+        assert_eq!(
+            DStr::from("hi").convert(&Conversion::Bytes),
+            Ok(Value::Bytes(DStr::from("hi")))
+        );
+        assert_eq!(
+            DStr::from("-7").convert(&Conversion::Integer),
+            Ok(Value::Integer(-7))
+        );
+        assert_eq!(
+            DStr::from("3.5").convert(&Conversion::Float),
+            Ok(Value::Float(3.5))
+        );
+        assert_eq!(
+            DStr::from("true").convert(&Conversion::Boolean),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            DStr::from("not-a-number").convert(&Conversion::Integer),
+            Err(ConversionError::Parse {
+                ty: "integer",
+                input: "not-a-number".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn convert_timestamp() {
+        let v = DStr::from("2021-03-04T05:06:07+00:00")
+            .convert(&Conversion::Timestamp)
+            .unwrap();
+        match v {
+            Value::Timestamp(dt) => assert_eq!(dt.to_rfc3339(), "2021-03-04T05:06:07+00:00"),
+            _ => panic!("expecting a timestamp"),
+        }
+    }
+
     #[test]
     fn from_testing() {
         let s = DStr::from("Hello, world");
@@ -1,3 +1,5 @@
+use crate::DStr;
+use parking_lot::Mutex;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -5,28 +7,137 @@ use std::sync::{
 
 const O: Ordering = Ordering::Relaxed;
 
-/// An atomic, thread shareable boolean switch.
-pub struct Switch(Arc<AtomicBool>);
+/// A resettable, hierarchical cancellation token.
+///
+/// A `CancelToken` is an atomic, thread shareable switch that also carries an optional
+/// human-readable [reason](CancelToken::reason) for the cancellation and can be arranged into a
+/// tree: a [`child`](CancelToken::child) reports cancelled when either it or _any_ of its
+/// ancestors is cancelled, which lets a parent "shutdown" cancel all its children at once while
+/// each child can still be cancelled individually. Cloning shares the underlying state.
+///
+/// Unlike a write-once flag, a token can be [`flip_off`](CancelToken::flip_off) or
+/// [`reset`](CancelToken::reset) and reused.
+#[derive(Clone)]
+pub struct CancelToken {
+    flag: Arc<AtomicBool>,
+    reason: Arc<Mutex<Option<DStr>>>,
+    parent: Option<Arc<CancelToken>>,
+}
 
-impl Switch {
-    /// A new switch set to 'off' (`false`).
+impl CancelToken {
+    /// A new token set to 'off' (not cancelled), with no parent.
     pub fn off() -> Self {
-        Switch(Arc::new(AtomicBool::new(false)))
+        CancelToken {
+            flag: Arc::new(AtomicBool::new(false)),
+            reason: Arc::new(Mutex::new(None)),
+            parent: None,
+        }
     }
 
-    /// Get the value of the switch.
+    /// Get the value of the token: `true` if this token or any ancestor is cancelled.
     pub fn get(&self) -> bool {
-        self.0.load(O)
+        self.flag.load(O) || self.parent.as_ref().map_or(false, |p| p.get())
     }
 
-    /// Flip the switch to the 'on' (`true`) position.
+    /// Flip the token to the 'on' (cancelled) position.
     pub fn flip_on(&self) {
-        self.0.store(true, O);
+        self.flag.store(true, O);
+    }
+
+    /// Flip the token back to the 'off' (not cancelled) position.
+    ///
+    /// Only affects this token; an ancestor's cancellation is untouched. The reason, if any, is
+    /// cleared. See [`reset`](CancelToken::reset), which is an alias that reads better when
+    /// re-arming a token for a fresh job.
+    pub fn flip_off(&self) {
+        *self.reason.lock() = None;
+        self.flag.store(false, O);
+    }
+
+    /// Reset the token to its initial, uncancelled state, ready to be reused for another job.
+    pub fn reset(&self) {
+        self.flip_off();
+    }
+
+    /// Flip the token on, recording a human-readable reason for the cancellation.
+    pub fn cancel_with(&self, reason: DStr) {
+        *self.reason.lock() = Some(reason);
+        self.flip_on();
+    }
+
+    /// The reason this token reports cancelled, if one was given.
+    ///
+    /// Returns this token's own reason when it is locally cancelled, otherwise the reason of the
+    /// nearest cancelled ancestor.
+    pub fn reason(&self) -> Option<DStr> {
+        if self.flag.load(O) {
+            return self.reason.lock().clone();
+        }
+        self.parent.as_ref().and_then(|p| p.reason())
+    }
+
+    /// Create a child token, cancelled whenever it or any of its ancestors is cancelled.
+    pub fn child(&self) -> CancelToken {
+        CancelToken {
+            flag: Arc::new(AtomicBool::new(false)),
+            reason: Arc::new(Mutex::new(None)),
+            parent: Some(Arc::new(self.clone())),
+        }
     }
 }
 
-impl Clone for Switch {
-    fn clone(&self) -> Self {
-        Switch(Arc::clone(&self.0))
+/// An atomic, thread shareable boolean switch.
+///
+/// Retained as a thin alias of [`CancelToken`] so existing call sites keep compiling.
+pub type Switch = CancelToken;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ancestor_cancels_descendants() {
+        let parent = CancelToken::off();
+        let child = parent.child();
+        let grandchild = child.child();
+
+        assert!(!grandchild.get());
+        parent.flip_on();
+        assert!(child.get());
+        assert!(grandchild.get());
+    }
+
+    #[test]
+    fn child_cancels_individually() {
+        let parent = CancelToken::off();
+        let child = parent.child();
+
+        child.flip_on();
+        assert!(child.get());
+        assert!(!parent.get());
+    }
+
+    #[test]
+    fn reason_propagates_from_ancestor() {
+        let parent = CancelToken::off();
+        let child = parent.child();
+
+        assert_eq!(child.reason(), None);
+        parent.cancel_with(DStr::from("shutting down"));
+        assert!(child.get());
+        assert_eq!(child.reason(), Some(DStr::from("shutting down")));
+    }
+
+    #[test]
+    fn reset_clears_flag_and_reason() {
+        let token = CancelToken::off();
+
+        token.cancel_with(DStr::from("stop"));
+        assert!(token.get());
+        assert_eq!(token.reason(), Some(DStr::from("stop")));
+
+        token.reset();
+        assert!(!token.get());
+        assert_eq!(token.reason(), None);
     }
 }